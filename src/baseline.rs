@@ -0,0 +1,156 @@
+// Persistent device baseline and anomaly scoring across sessions.
+//
+// Stores every device this tool has ever observed, keyed by
+// `vendor_id:product_id`, so a later run can tell a device it has seen many
+// times apart from a brand-new one — and, more importantly, flag a known ID
+// whose descriptors have changed, which is how spoofed/cloned devices
+// present. The serial number lives inside the snapshot (not the key) so a
+// device that suddenly reports an empty serial is compared against its own
+// prior baseline rather than falling through as "new".
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+const STATE_PATH: &str = "/var/lib/usb-monitor/baseline.state";
+
+pub type DeviceKey = (u16, u16);
+
+/// The subset of a device's descriptors that matters for spoofing detection.
+/// Deliberately excludes transient fields like bus/address.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceSnapshot {
+    pub device_class: u8,
+    pub max_power_ma: u16,
+    /// Sorted `(class, subclass, protocol)` for every interface, across all
+    /// configurations.
+    pub interface_classes: Vec<(u8, u8, u8)>,
+    pub serial_number_present: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BaselineRecord {
+    snapshot: DeviceSnapshot,
+    times_seen: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct BaselineFile {
+    #[serde(default)]
+    devices: HashMap<String, BaselineRecord>,
+}
+
+pub struct BaselineStore {
+    records: HashMap<DeviceKey, BaselineRecord>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AnomalyScore {
+    /// 0 = identical to baseline, higher = more suspicious.
+    pub score: u8,
+    pub label: &'static str,
+    pub reasons: Vec<String>,
+}
+
+impl BaselineStore {
+    pub fn load() -> Self {
+        let Ok(contents) = std::fs::read_to_string(STATE_PATH) else {
+            return BaselineStore { records: HashMap::new() };
+        };
+        let Ok(file) = serde_json::from_str::<BaselineFile>(&contents) else {
+            return BaselineStore { records: HashMap::new() };
+        };
+
+        let records = file
+            .devices
+            .into_iter()
+            .filter_map(|(key, record)| parse_key(&key).map(|k| (k, record)))
+            .collect();
+
+        BaselineStore { records }
+    }
+
+    pub fn save(&self) {
+        let devices = self
+            .records
+            .iter()
+            .map(|(key, record)| (format_key(key), record.clone()))
+            .collect();
+        let file = BaselineFile { devices };
+
+        let Ok(contents) = serde_json::to_string_pretty(&file) else {
+            return;
+        };
+        if let Some(parent) = PathBuf::from(STATE_PATH).parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Err(e) = std::fs::write(STATE_PATH, contents) {
+            eprintln!("⚠️ Failed to persist device baseline: {}", e);
+        }
+    }
+
+    /// Compare `snapshot` against the stored baseline for `key`, score the
+    /// anomaly, then record `snapshot` as the new baseline for next time.
+    pub fn observe(&mut self, key: DeviceKey, snapshot: DeviceSnapshot) -> AnomalyScore {
+        let result = match self.records.get(&key) {
+            None => AnomalyScore {
+                score: 40,
+                label: "new device",
+                reasons: vec!["First time this vendor:product has been seen".to_string()],
+            },
+            Some(existing) if existing.snapshot == snapshot => AnomalyScore {
+                score: 0,
+                label: "known, unchanged",
+                reasons: Vec::new(),
+            },
+            Some(existing) => {
+                let mut reasons = Vec::new();
+
+                if existing.snapshot.device_class != snapshot.device_class {
+                    reasons.push(format!(
+                        "device_class changed 0x{:02x} -> 0x{:02x}",
+                        existing.snapshot.device_class, snapshot.device_class
+                    ));
+                }
+                if existing.snapshot.interface_classes != snapshot.interface_classes {
+                    reasons.push(format!(
+                        "interface set changed: {:?} -> {:?}",
+                        existing.snapshot.interface_classes, snapshot.interface_classes
+                    ));
+                }
+                if existing.snapshot.max_power_ma != snapshot.max_power_ma {
+                    reasons.push(format!(
+                        "max_power_ma changed {} -> {}",
+                        existing.snapshot.max_power_ma, snapshot.max_power_ma
+                    ));
+                }
+                if existing.snapshot.serial_number_present && !snapshot.serial_number_present {
+                    reasons.push("serial number was previously present but is now empty".to_string());
+                }
+
+                AnomalyScore {
+                    score: 90,
+                    label: "known ID, descriptors changed (possible spoofing)",
+                    reasons,
+                }
+            }
+        };
+
+        let times_seen = self.records.get(&key).map(|r| r.times_seen).unwrap_or(0) + 1;
+        self.records.insert(key, BaselineRecord { snapshot, times_seen });
+        self.save();
+
+        result
+    }
+}
+
+fn format_key(key: &DeviceKey) -> String {
+    format!("{:04x}:{:04x}", key.0, key.1)
+}
+
+fn parse_key(key: &str) -> Option<DeviceKey> {
+    let mut parts = key.splitn(2, ':');
+    let vendor_id = u16::from_str_radix(parts.next()?, 16).ok()?;
+    let product_id = u16::from_str_radix(parts.next()?, 16).ok()?;
+    Some((vendor_id, product_id))
+}
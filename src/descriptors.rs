@@ -0,0 +1,208 @@
+// Descriptor-tree walking and BadUSB-style composite-device detection.
+//
+// `rusb`'s top-level `DeviceDescriptor` only tells you the declared device
+// class; the interesting attack surface lives one level down, in the
+// configuration/interface/endpoint tree, where a device can expose
+// interfaces that contradict what it claims to be at the top level (the
+// classic "thumb drive that is secretly also a keyboard" BadUSB pattern).
+
+use rusb::{Device, UsbContext};
+
+pub const HID_CLASS: u8 = 0x03;
+pub const HID_KEYBOARD_SUBCLASS: u8 = 0x01;
+pub const HID_KEYBOARD_PROTOCOL: u8 = 0x01;
+pub const MASS_STORAGE_CLASS: u8 = 0x08;
+pub const HUB_CLASS: u8 = 0x09;
+pub const CDC_CONTROL_CLASS: u8 = 0x02;
+pub const CDC_DATA_CLASS: u8 = 0x0A;
+/// Top-level class code meaning "see the interface descriptors instead".
+pub const INTERFACE_DEFINED_CLASS: u8 = 0x00;
+
+/// Device classes that are legitimately multi-interface at the device level
+/// (e.g. a CDC/ACM modem always pairs a control interface with a data
+/// interface), so exposing more than one interface in a configuration is not
+/// on its own evidence of a composite-device mismatch.
+const INHERENTLY_MULTI_INTERFACE_CLASSES: &[u8] = &[CDC_CONTROL_CLASS];
+
+#[derive(Debug, Clone)]
+pub struct EndpointInfo {
+    pub address: u8,
+    pub transfer_type: String,
+    pub direction: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct InterfaceInfo {
+    pub interface_number: u8,
+    pub alternate_setting: u8,
+    pub class: u8,
+    pub subclass: u8,
+    pub protocol: u8,
+    pub endpoints: Vec<EndpointInfo>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigInfo {
+    pub configuration_value: u8,
+    pub interfaces: Vec<InterfaceInfo>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DescriptorTree {
+    pub configurations: Vec<ConfigInfo>,
+}
+
+impl InterfaceInfo {
+    pub fn is_hid_keyboard(&self) -> bool {
+        self.class == HID_CLASS
+            && self.subclass == HID_KEYBOARD_SUBCLASS
+            && self.protocol == HID_KEYBOARD_PROTOCOL
+    }
+
+    pub fn is_networking(&self) -> bool {
+        self.class == CDC_CONTROL_CLASS || self.class == CDC_DATA_CLASS
+    }
+}
+
+/// Walk every configuration, interface, and endpoint descriptor for `device`.
+pub fn walk_descriptor_tree<T: UsbContext>(device: &Device<T>, num_configurations: u8) -> DescriptorTree {
+    let mut configurations = Vec::new();
+
+    for config_index in 0..num_configurations {
+        let Ok(config) = device.config_descriptor(config_index) else {
+            continue;
+        };
+
+        // `interface.descriptors()` yields one entry per *alternate setting*
+        // of the interface, not per interface — an ordinary multi-alt-setting
+        // device (a UVC webcam, a UAC audio device) would otherwise be
+        // counted and reported as if it had one interface per alt setting.
+        // Take the default alternate setting (0) as the interface's
+        // representative descriptor, falling back to the first one if a
+        // device omits alt setting 0.
+        let mut interfaces = Vec::new();
+        for interface in config.interfaces() {
+            let alt_settings: Vec<_> = interface.descriptors().collect();
+            let descriptor = alt_settings
+                .iter()
+                .find(|d| d.setting_number() == 0)
+                .or_else(|| alt_settings.first());
+            let Some(descriptor) = descriptor else {
+                continue;
+            };
+
+            let endpoints = descriptor
+                .endpoint_descriptors()
+                .map(|ep| EndpointInfo {
+                    address: ep.address(),
+                    transfer_type: format!("{:?}", ep.transfer_type()),
+                    direction: format!("{:?}", ep.direction()),
+                })
+                .collect();
+
+            interfaces.push(InterfaceInfo {
+                interface_number: descriptor.interface_number(),
+                alternate_setting: descriptor.setting_number(),
+                class: descriptor.class_code(),
+                subclass: descriptor.sub_class_code(),
+                protocol: descriptor.protocol_code(),
+                endpoints,
+            });
+        }
+
+        configurations.push(ConfigInfo {
+            configuration_value: config.number(),
+            interfaces,
+        });
+    }
+
+    DescriptorTree { configurations }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BadUsbVerdict {
+    /// One human-readable line per interface, for inclusion in the report.
+    pub interface_summary: Vec<String>,
+    /// Raised flags describing composite-device mismatches.
+    pub flags: Vec<String>,
+}
+
+impl BadUsbVerdict {
+    pub fn is_suspicious(&self) -> bool {
+        !self.flags.is_empty()
+    }
+}
+
+/// Compare the declared top-level `device_class` against what the interface
+/// tree actually exposes, flagging the classic BadUSB pattern: a device that
+/// declares mass storage, a hub, or "interface-defined" (0x00) at the top
+/// level, but that also presents an HID keyboard or networking interface
+/// underneath.
+pub fn analyze_badusb(device_class: u8, tree: &DescriptorTree) -> BadUsbVerdict {
+    let mut verdict = BadUsbVerdict::default();
+
+    let looks_benign_at_top_level = matches!(
+        device_class,
+        MASS_STORAGE_CLASS | HUB_CLASS | INTERFACE_DEFINED_CLASS
+    );
+
+    let mut max_interfaces_per_config = 0;
+    let mut has_keyboard = false;
+    let mut has_networking = false;
+
+    for config in &tree.configurations {
+        max_interfaces_per_config = max_interfaces_per_config.max(config.interfaces.len());
+
+        for iface in &config.interfaces {
+            let endpoint_summary = iface
+                .endpoints
+                .iter()
+                .map(|ep| format!("{:#04x} {} {}", ep.address, ep.direction, ep.transfer_type))
+                .collect::<Vec<_>>()
+                .join(", ");
+            verdict.interface_summary.push(format!(
+                "config {} / interface {}.{}: class 0x{:02x} subclass 0x{:02x} protocol 0x{:02x} endpoints: [{}]",
+                config.configuration_value,
+                iface.interface_number,
+                iface.alternate_setting,
+                iface.class,
+                iface.subclass,
+                iface.protocol,
+                endpoint_summary
+            ));
+
+            if iface.is_hid_keyboard() {
+                has_keyboard = true;
+            }
+            if iface.is_networking() {
+                has_networking = true;
+            }
+        }
+    }
+
+    if looks_benign_at_top_level && has_keyboard {
+        verdict.flags.push(format!(
+            "⚠️ Device declares class 0x{:02x} (storage/hub/interface-defined) but exposes an HID keyboard interface — classic BadUSB pattern",
+            device_class
+        ));
+    }
+
+    if looks_benign_at_top_level && has_networking {
+        verdict.flags.push(format!(
+            "⚠️ Device declares class 0x{:02x} but exposes a CDC/networking interface — possible covert network implant",
+            device_class
+        ));
+    }
+
+    if device_class != INTERFACE_DEFINED_CLASS
+        && !INHERENTLY_MULTI_INTERFACE_CLASSES.contains(&device_class)
+        && max_interfaces_per_config > 1
+    {
+        verdict.flags.push(format!(
+            "⚠️ Device declares a single-function class 0x{:02x} but exposes {} interfaces in one configuration",
+            device_class, max_interfaces_per_config
+        ));
+    }
+
+    verdict
+}
@@ -0,0 +1,68 @@
+// Event-driven hotplug monitoring, replacing the 1-second polling loop.
+//
+// `rusb`/libusb can deliver `Arrived`/`Left` callbacks directly from the
+// platform's USB stack, so a device that attaches and detaches between poll
+// ticks (exactly the re-enumeration trick malicious USB implants use to
+// evade detection) is still caught. `main` falls back to
+// `run_polling_loop` when hotplug support isn't available.
+
+use rusb::{Context, Device, Hotplug, HotplugBuilder, UsbContext};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{get_device_info, handle_new_device, MonitorState};
+
+struct HotplugHandler<T: UsbContext> {
+    state: Arc<MonitorState>,
+    context: T,
+}
+
+impl<T: UsbContext> Hotplug<T> for HotplugHandler<T> {
+    fn device_arrived(&mut self, device: Device<T>) {
+        let Ok(device_desc) = device.device_descriptor() else {
+            return;
+        };
+
+        let device_info = get_device_info(&device, &device_desc, &self.context);
+        let arrival_timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        handle_new_device(
+            device.bus_number(),
+            device.address(),
+            &device_info,
+            &self.state,
+            Some(arrival_timestamp),
+        );
+    }
+
+    fn device_left(&mut self, device: Device<T>) {
+        println!(
+            "🔌➖ USB device left: Bus {:03} Device {:03}",
+            device.bus_number(),
+            device.address()
+        );
+    }
+}
+
+/// Registers hotplug callbacks and blocks forever, dispatching `Arrived`
+/// callbacks into the same analysis pipeline the polling loop uses.
+/// `enumerate(true)` fires an `Arrived` callback for every device already
+/// plugged in at registration time, so no separate initial scan is needed.
+pub(crate) fn run(state: Arc<MonitorState>) -> rusb::Result<()> {
+    let context = Context::new()?;
+    let handler = HotplugHandler {
+        state,
+        context: context.clone(),
+    };
+
+    let _registration = HotplugBuilder::new()
+        .enumerate(true)
+        .register(&context, Box::new(handler))?;
+
+    loop {
+        context.handle_events(Some(Duration::from_secs(1)))?;
+    }
+}
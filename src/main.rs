@@ -1,6 +1,18 @@
 use rusb::{Context, Device, DeviceDescriptor, UsbContext, Speed};
 use std::{thread, time::Duration};
 use std::collections::HashMap;
+use std::path::PathBuf;
+
+mod baseline;
+mod descriptors;
+mod hotplug;
+mod policy;
+mod quarantine;
+use baseline::BaselineStore;
+use descriptors::DescriptorTree;
+use policy::{FingerprintDb, Policy};
+use quarantine::QuarantineManager;
+use std::sync::{Arc, Mutex};
 
 // Function to detect and return the current OS
 fn get_current_os() -> &'static str {
@@ -47,21 +59,49 @@ fn check_os_compatibility() -> (bool, &'static str) {
 
 // Define a struct to store device information
 #[derive(Debug, Clone)]
-struct UsbDeviceInfo {
-    vendor_id: u16,
-    product_id: u16,
-    manufacturer: String,
-    product_name: String,
-    serial_number: String,
-    max_power_ma: u16,
-    speed: u32,        // Speed value in Mbit/s
-    device_class: u8,  // Added field for device class
-    device_subclass: u8,
-    device_protocol: u8,
-    num_configurations: u8,
+pub(crate) struct UsbDeviceInfo {
+    pub(crate) vendor_id: u16,
+    pub(crate) product_id: u16,
+    pub(crate) manufacturer: String,
+    pub(crate) product_name: String,
+    pub(crate) serial_number: String,
+    pub(crate) max_power_ma: u16,
+    pub(crate) speed: u32,        // Speed value in Mbit/s
+    pub(crate) device_class: u8,  // Added field for device class
+    pub(crate) device_subclass: u8,
+    pub(crate) device_protocol: u8,
+    pub(crate) num_configurations: u8,
+    pub(crate) sysfs_path: Option<PathBuf>, // Linux only: path under /sys/bus/usb/devices used for quarantine
+    pub(crate) descriptor_tree: DescriptorTree,
+}
+
+/// Shared state threaded through both the legacy polling loop and the
+/// hotplug arrival callback, so the same analysis pipeline runs either way.
+pub(crate) struct MonitorState {
+    pub(crate) current_os: &'static str,
+    pub(crate) auto_quarantine: bool,
+    pub(crate) fingerprint_db: FingerprintDb,
+    pub(crate) quarantine_manager: Mutex<QuarantineManager>,
+    pub(crate) baseline_store: Mutex<BaselineStore>,
 }
 
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+
+    // `enable`/`disable`/`list` are one-shot quarantine management verbs;
+    // handle them and exit instead of starting the monitor loop.
+    if let Some(verb) = args.get(1) {
+        match verb.as_str() {
+            "enable" | "disable" | "list" => {
+                run_quarantine_cli(verb, args.get(2));
+                return;
+            }
+            _ => {}
+        }
+    }
+
+    let auto_quarantine = args.iter().any(|a| a == "--auto-quarantine");
+
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
     println!("📱 USB Device Monitor Started");
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
@@ -73,71 +113,224 @@ fn main() {
         return;
     }
 
+    if auto_quarantine {
+        println!("🛡️ Auto-quarantine enabled: devices with High-confidence detections will be deauthorized");
+    }
 
-    // Initial device list
+    let quarantine_manager = QuarantineManager::load();
+
+    let policy_file_path = args
+        .iter()
+        .position(|a| a == "--policy-file")
+        .and_then(|i| args.get(i + 1))
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(|| std::path::PathBuf::from("usb_policy.toml"));
+    let fingerprint_db = match FingerprintDb::load(&policy_file_path) {
+        Ok(db) => db,
+        Err(e) => {
+            println!("ℹ️ Could not load policy file {} ({}) — using built-in fingerprint list only", policy_file_path.display(), e);
+            FingerprintDb::builtin()
+        }
+    };
+
+    let state = Arc::new(MonitorState {
+        current_os,
+        auto_quarantine,
+        fingerprint_db,
+        quarantine_manager: Mutex::new(quarantine_manager),
+        baseline_store: Mutex::new(BaselineStore::load()),
+    });
+
+    let force_poll = args.iter().any(|a| a == "--poll");
+
+    if !force_poll && rusb::has_hotplug() {
+        println!("🔌 Hotplug support detected — monitoring USB attach/detach events in real time");
+        if let Err(e) = hotplug::run(Arc::clone(&state)) {
+            eprintln!("⚠️ Hotplug monitor failed ({}), falling back to polling", e);
+            run_polling_loop(&state);
+        }
+    } else {
+        println!("🔁 Hotplug unavailable or --poll passed — falling back to 1-second polling");
+        run_polling_loop(&state);
+    }
+}
+
+/// Fallback monitor loop for platforms where libusb hotplug support is
+/// unavailable, or when explicitly requested via `--poll`. Diffs a
+/// `HashMap` snapshot every second; this can miss devices that attach and
+/// detach between polls, which is exactly the `hotplug::run` path exists
+/// to fix.
+fn run_polling_loop(state: &MonitorState) {
     let mut previous_devices: HashMap<(u8, u8), UsbDeviceInfo> = get_device_list();
 
     loop {
         let current_devices = get_device_list();
 
-        // Check for new devices
         for ((bus, address), device_info) in &current_devices {
             if !previous_devices.contains_key(&(*bus, *address)) {
-                let detection = def_analysis_voltage_and_speed(device_info.max_power_ma, device_info.speed);
-                let (kernel_message, suspicious_flags) = def_check_kernel_logs(current_os);
-                println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-                println!("📌 New USB device connected:");
-                println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-                println!("  📍 Location:       Bus {:03} Device {:03}", bus, address);
-                println!("  🆔 Device ID:      {:04x}:{:04x}", device_info.vendor_id, device_info.product_id);
-                
-                if !device_info.manufacturer.is_empty() {
-                    println!("  🏭 Manufacturer:   {}", device_info.manufacturer);
-                }
-                
-                if !device_info.product_name.is_empty() {
-                    println!("  📦 Product:        {}", device_info.product_name);
-                }
-                
-                if !device_info.serial_number.is_empty() {
-                    println!("  🔢 Serial Number:  {}", device_info.serial_number);
-                }
-                
-                // Display class information
-                println!("  📑 Device Class:   0x{:02x} ({})", device_info.device_class, get_class_name(device_info.device_class));
-                println!("  📄 SubClass:       0x{:02x}", device_info.device_subclass);
-                println!("  📃 Protocol:       0x{:02x}", device_info.device_protocol);
-                println!("  🔌 Configurations: {}", device_info.num_configurations);
-                
-                // Display power information
-                println!("  ⚡ Max Power:      {} mA", device_info.max_power_ma);
-                
-                // Display speed information
-                println!("  🚀 Speed:          {} Mbit/s", device_info.speed);
-                
-                println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-
-                println!("Detection Result:");
-
-                println!("Voltage and Speed Detection:  {}", detection);
-                println!("Kernel Log Check:            {}", kernel_message);
-                if !suspicious_flags.is_empty() {
-                    println!("⚠️ WARNINGS:");
-                    for flag in suspicious_flags {
-                        println!("  {}", flag);
-                    }
-                }
+                handle_new_device(*bus, *address, device_info, state, None);
             }
         }
 
-        // Update the previous_devices list
         previous_devices = current_devices;
 
-        // Sleep for a while before checking again
         thread::sleep(Duration::from_secs(1));
     }
 }
 
+/// Runs the full analysis/reporting pipeline for a newly observed device and
+/// applies policy/quarantine decisions. Called from both the polling loop
+/// and the hotplug arrival callback. `arrival_timestamp`, when present, is
+/// printed so hotplug callers can report the exact arrival time.
+pub(crate) fn handle_new_device(
+    bus: u8,
+    address: u8,
+    device_info: &UsbDeviceInfo,
+    state: &MonitorState,
+    arrival_timestamp: Option<u64>,
+) {
+    let detection = def_analysis_voltage_and_speed(device_info.max_power_ma, device_info.speed);
+    let (kernel_message, mut suspicious_flags) = def_check_kernel_logs(state.current_os);
+    let badusb_verdict = descriptors::analyze_badusb(device_info.device_class, &device_info.descriptor_tree);
+    suspicious_flags.extend(badusb_verdict.flags.clone());
+
+    let policy_verdict = state.fingerprint_db.lookup(
+        device_info.vendor_id,
+        device_info.product_id,
+        &device_info.serial_number,
+    );
+
+    let baseline_key = (device_info.vendor_id, device_info.product_id);
+    let mut interface_classes: Vec<(u8, u8, u8)> = device_info
+        .descriptor_tree
+        .configurations
+        .iter()
+        .flat_map(|c| c.interfaces.iter())
+        .map(|i| (i.class, i.subclass, i.protocol))
+        .collect();
+    interface_classes.sort();
+    let snapshot = baseline::DeviceSnapshot {
+        device_class: device_info.device_class,
+        max_power_ma: device_info.max_power_ma,
+        interface_classes,
+        serial_number_present: !device_info.serial_number.is_empty(),
+    };
+    let anomaly = state.baseline_store.lock().unwrap().observe(baseline_key, snapshot);
+
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    println!("📌 New USB device connected:");
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    if let Some(timestamp) = arrival_timestamp {
+        println!("  🕒 Arrived at:     unix timestamp {}", timestamp);
+    }
+    println!("  📍 Location:       Bus {:03} Device {:03}", bus, address);
+    println!("  🆔 Device ID:      {:04x}:{:04x}", device_info.vendor_id, device_info.product_id);
+
+    // Fall back to the fingerprint table for manufacturer/product
+    // name when the string descriptors came back empty (common
+    // when `device.open()` fails due to permissions).
+    let fingerprint_name = policy_verdict.as_ref().and_then(|v| v.name.clone());
+    let manufacturer = if !device_info.manufacturer.is_empty() {
+        device_info.manufacturer.clone()
+    } else {
+        fingerprint_name.clone().unwrap_or_default()
+    };
+    let product_name = if !device_info.product_name.is_empty() {
+        device_info.product_name.clone()
+    } else {
+        fingerprint_name.clone().unwrap_or_default()
+    };
+
+    if !manufacturer.is_empty() {
+        println!("  🏭 Manufacturer:   {}", manufacturer);
+    }
+
+    if !product_name.is_empty() {
+        println!("  📦 Product:        {}", product_name);
+    }
+
+    if !device_info.serial_number.is_empty() {
+        println!("  🔢 Serial Number:  {}", device_info.serial_number);
+    }
+
+    // Display class information
+    println!("  📑 Device Class:   0x{:02x} ({})", device_info.device_class, get_class_name(device_info.device_class));
+    println!("  📄 SubClass:       0x{:02x}", device_info.device_subclass);
+    println!("  📃 Protocol:       0x{:02x}", device_info.device_protocol);
+    println!("  🔌 Configurations: {}", device_info.num_configurations);
+
+    // Display the full interface tree (composite-device structure)
+    println!("  🧩 Interfaces:");
+    for iface_summary in &badusb_verdict.interface_summary {
+        println!("      {}", iface_summary);
+    }
+    println!(
+        "  🧬 BadUSB Check:   {}",
+        if badusb_verdict.is_suspicious() { "suspicious" } else { "clean" }
+    );
+
+    // Display power information
+    println!("  ⚡ Max Power:      {} mA", device_info.max_power_ma);
+
+    // Display speed information
+    println!("  🚀 Speed:          {} Mbit/s", device_info.speed);
+
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+
+    println!("Detection Result:");
+
+    println!("Voltage and Speed Detection:  {}", detection);
+    println!("Kernel Log Check:            {}", kernel_message);
+    println!("Baseline Anomaly Score:      {} ({})", anomaly.score, anomaly.label);
+    for reason in &anomaly.reasons {
+        println!("  ↳ {}", reason);
+    }
+
+    match policy_verdict.as_ref().map(|v| v.policy) {
+        Some(Policy::Allow) => println!("Policy:                       allow (known device, suppressing warnings)"),
+        Some(Policy::Deny) => println!("Policy:                       🚨 DENY — known attack-tool fingerprint"),
+        Some(Policy::Watch) => println!("Policy:                       watch"),
+        None => println!("Policy:                       watch (unknown device)"),
+    }
+
+    let is_denied = matches!(policy_verdict.as_ref().map(|v| v.policy), Some(Policy::Deny));
+    let is_allowed = matches!(policy_verdict.as_ref().map(|v| v.policy), Some(Policy::Allow));
+
+    if !is_allowed && !suspicious_flags.is_empty() {
+        println!("⚠️ WARNINGS:");
+        for flag in &suspicious_flags {
+            println!("  {}", flag);
+        }
+    }
+
+    let voltage_high_confidence = state.auto_quarantine && detection.contains("High confidence") && !is_allowed;
+    let badusb_high_confidence = state.auto_quarantine && badusb_verdict.is_suspicious() && !is_allowed;
+
+    if voltage_high_confidence || badusb_high_confidence || is_denied {
+        match &device_info.sysfs_path {
+            Some(sysfs_path) => {
+                let key = (device_info.vendor_id, device_info.product_id);
+                let mut quarantine_manager = state.quarantine_manager.lock().unwrap();
+                if let Err(e) = quarantine_manager.disable(key, sysfs_path) {
+                    eprintln!("⚠️ Failed to auto-quarantine device: {}", e);
+                } else {
+                    // Also stop the controller from auto-authorizing any new
+                    // device plugged in anywhere on the same bus. The original
+                    // authorized_default value is recorded against this
+                    // device's record so `enable` can restore it.
+                    let controller_path = quarantine::controller_sysfs_path(bus);
+                    if let Err(e) = quarantine_manager.disable_controller_default(key, &controller_path) {
+                        eprintln!("⚠️ Failed to disable controller authorized_default: {}", e);
+                    }
+                }
+            }
+            None => {
+                eprintln!("⚠️ Cannot auto-quarantine: no sysfs path available for this device");
+            }
+        }
+    }
+}
+
 // Function to get a list of connected USB devices with detailed information
 fn get_device_list() -> HashMap<(u8, u8), UsbDeviceInfo> {
     let mut device_map = HashMap::new();
@@ -166,7 +359,7 @@ fn get_device_list() -> HashMap<(u8, u8), UsbDeviceInfo> {
     device_map
 }
 
-fn get_device_info<T: UsbContext>(
+pub(crate) fn get_device_info<T: UsbContext>(
     device: &Device<T>,
     device_desc: &DeviceDescriptor,
     _context: &T,
@@ -234,10 +427,21 @@ fn get_device_info<T: UsbContext>(
     // Get power information from configuration descriptor (does not require open)
     if let Ok(config) = device.config_descriptor(0) {
         let power_units = config.max_power();
-        
+
         max_power_ma = power_units as u16;
     }
 
+    #[cfg(target_os = "linux")]
+    let sysfs_path = device
+        .port_numbers()
+        .ok()
+        .map(|ports| quarantine::sysfs_path_for(device.bus_number(), &ports));
+
+    #[cfg(not(target_os = "linux"))]
+    let sysfs_path = None;
+
+    let descriptor_tree = descriptors::walk_descriptor_tree(device, num_configurations);
+
     UsbDeviceInfo {
         vendor_id,
         product_id,
@@ -250,6 +454,8 @@ fn get_device_info<T: UsbContext>(
         device_subclass,
         device_protocol,
         num_configurations,
+        sysfs_path,
+        descriptor_tree,
     }
 }
 
@@ -367,8 +573,91 @@ fn def_check_kernel_logs(operating_system: &str) -> (&'static str, Vec<String>)
         }    
         "macOS" => {
             println!("Checking system logs for macOS...");
-            ("System logs checked for macOS", Vec::new())
-        }    
+
+            let mut suspicious_flags = Vec::new();
+
+            // Recent USB attach/detach events from the unified log.
+            match std::process::Command::new("log")
+                .args([
+                    "show",
+                    "--predicate",
+                    "subsystem == \"com.apple.iokit.IOUSBHostFamily\"",
+                    "--last",
+                    "1m",
+                    "--style",
+                    "compact",
+                ])
+                .output()
+            {
+                Ok(output) => {
+                    let log_output = String::from_utf8_lossy(&output.stdout);
+                    let suspicious_keywords = ["device reset occurred", "Mfr=0, Product=0", "re-enumerat"];
+
+                    for line in log_output.lines().take(40) {
+                        for keyword in &suspicious_keywords {
+                            if line.to_lowercase().contains(&keyword.to_lowercase()) {
+                                suspicious_flags.push(format!(
+                                    "⚠️ Suspicious keyword '{}' found in unified log: {}",
+                                    keyword, line
+                                ));
+                            }
+                        }
+                    }
+                }
+                Err(_) => {
+                    suspicious_flags.push("⚠️ Failed to run `log show` for IOUSBHostFamily events".to_string());
+                }
+            }
+
+            // Inspect the live device tree reported by IOKit, looking for
+            // devices with empty descriptor strings or duplicate location IDs
+            // (both symptomatic of a re-enumerating implant). The IOKit list
+            // is not compared against the devices `rusb` enumerated.
+            match std::process::Command::new("ioreg")
+                .args(["-p", "IOUSB", "-l", "-w", "0"])
+                .output()
+            {
+                Ok(output) => {
+                    let ioreg_output = String::from_utf8_lossy(&output.stdout);
+                    let devices = parse_ioreg_usb_devices(&ioreg_output);
+
+                    let mut seen_locations: HashMap<String, u32> = HashMap::new();
+                    for device in &devices {
+                        if let Some(location_id) = &device.location_id {
+                            *seen_locations.entry(location_id.clone()).or_insert(0) += 1;
+                        }
+
+                        if device.manufacturer.as_deref().unwrap_or("").is_empty()
+                            && device.product_name.as_deref().unwrap_or("").is_empty()
+                        {
+                            suspicious_flags.push(format!(
+                                "⚠️ IOKit device {:04x}:{:04x} reports no manufacturer or product string",
+                                device.vendor_id.unwrap_or(0),
+                                device.product_id.unwrap_or(0)
+                            ));
+                        }
+                    }
+
+                    for (location_id, count) in seen_locations {
+                        if count > 1 {
+                            suspicious_flags.push(format!(
+                                "⚠️ Location ID {} reported by {} IOKit devices simultaneously (possible re-enumeration)",
+                                location_id, count
+                            ));
+                        }
+                    }
+                }
+                Err(_) => {
+                    suspicious_flags.push("⚠️ Failed to run `ioreg -p IOUSB`".to_string());
+                }
+            }
+
+            if !suspicious_flags.is_empty() {
+                ("Suspicious USB activity detected in system logs!", suspicious_flags)
+            } else {
+                ("No suspicious signs of usb", suspicious_flags)
+            }
+        }
         _ => {
             println!("Unsupported operating system for kernel log checking.");
             ("Unsupported OS for log checking", Vec::new())
@@ -377,6 +666,139 @@ fn def_check_kernel_logs(operating_system: &str) -> (&'static str, Vec<String>)
 }
 
 
+#[derive(Debug, Default)]
+struct IoregUsbDevice {
+    vendor_id: Option<u16>,
+    product_id: Option<u16>,
+    location_id: Option<String>,
+    manufacturer: Option<String>,
+    product_name: Option<String>,
+}
+
+// Extracts per-device vendor/product IDs, location IDs, and
+// manufacturer/product strings from `ioreg -p IOUSB -l -w 0` output. Each
+// `+-o ... <class IOUSBHostDevice ...>` line starts a new device block; the
+// key/value pairs inside its `{ ... }` body are parsed line by line.
+fn parse_ioreg_usb_devices(ioreg_output: &str) -> Vec<IoregUsbDevice> {
+    let mut devices = Vec::new();
+    let mut current: Option<IoregUsbDevice> = None;
+
+    for line in ioreg_output.lines() {
+        if line.contains("+-o") && (line.contains("IOUSBHostDevice") || line.contains("IOUSBDevice")) {
+            if let Some(device) = current.take() {
+                devices.push(device);
+            }
+            current = Some(IoregUsbDevice::default());
+            continue;
+        }
+
+        let Some(device) = current.as_mut() else {
+            continue;
+        };
+
+        if let Some(value) = extract_ioreg_value(line, "idVendor") {
+            device.vendor_id = value.parse::<u16>().ok();
+        } else if let Some(value) = extract_ioreg_value(line, "idProduct") {
+            device.product_id = value.parse::<u16>().ok();
+        } else if let Some(value) = extract_ioreg_value(line, "locationID") {
+            device.location_id = Some(value);
+        } else if let Some(value) = extract_ioreg_value(line, "USB Vendor Name") {
+            device.manufacturer = Some(value);
+        } else if let Some(value) = extract_ioreg_value(line, "USB Product Name") {
+            device.product_name = Some(value);
+        }
+    }
+
+    if let Some(device) = current.take() {
+        devices.push(device);
+    }
+
+    devices
+}
+
+// Pulls the value out of an ioreg `"key" = value` line, stripping quotes.
+// Property lines are prefixed with tree-drawing guides whose depth tracks
+// nesting (e.g. `    | |   "idVendor" = 1452`), so the key can't be matched
+// with a plain `starts_with` after trimming whitespace — the leading `|`
+// guides have to be accounted for too.
+fn extract_ioreg_value(line: &str, key: &str) -> Option<String> {
+    let quoted_key = format!("\"{}\"", key);
+    let key_pos = line.find(&quoted_key)?;
+    if !line[..key_pos].chars().all(|c| matches!(c, ' ' | '|' | '+' | '-' | 'o')) {
+        return None;
+    }
+    let (_, value) = line[key_pos..].split_once('=')?;
+    Some(value.trim().trim_matches('"').to_string())
+}
+
+
+// Handles the `enable`, `disable`, and `list` quarantine verbs, e.g.
+// `usb-monitor disable 05ac:8289` or `usb-monitor list`.
+fn run_quarantine_cli(verb: &str, key_arg: Option<&String>) {
+    let mut manager = QuarantineManager::load();
+
+    match verb {
+        "list" => {
+            let records = manager.list();
+            if records.is_empty() {
+                println!("No quarantined devices on record.");
+                return;
+            }
+            println!("Quarantined devices:");
+            for ((vendor_id, product_id), record) in records {
+                println!(
+                    "  {:04x}:{:04x}  {}  quarantined={}  original_authorized={}",
+                    vendor_id,
+                    product_id,
+                    record.sysfs_path.display(),
+                    record.currently_quarantined,
+                    record.original_authorized
+                );
+            }
+        }
+        "enable" => {
+            let Some(key_arg) = key_arg else {
+                eprintln!("Usage: usb-monitor enable <vendor_id:product_id>");
+                return;
+            };
+            let Some(key) = quarantine::parse_key(key_arg) else {
+                eprintln!("Invalid device key '{}', expected vendor_id:product_id in hex", key_arg);
+                return;
+            };
+            if let Err(e) = manager.enable(key) {
+                eprintln!("⚠️ Failed to re-authorize device: {}", e);
+            }
+        }
+        "disable" => {
+            let Some(key_arg) = key_arg else {
+                eprintln!("Usage: usb-monitor disable <vendor_id:product_id>");
+                return;
+            };
+            let Some(key) = quarantine::parse_key(key_arg) else {
+                eprintln!("Invalid device key '{}', expected vendor_id:product_id in hex", key_arg);
+                return;
+            };
+
+            let devices = get_device_list();
+            let Some(device_info) = devices
+                .values()
+                .find(|d| (d.vendor_id, d.product_id) == key)
+            else {
+                eprintln!("Device {:04x}:{:04x} is not currently connected", key.0, key.1);
+                return;
+            };
+            let Some(sysfs_path) = &device_info.sysfs_path else {
+                eprintln!("No sysfs path available for {:04x}:{:04x}", key.0, key.1);
+                return;
+            };
+            if let Err(e) = manager.disable(key, sysfs_path) {
+                eprintln!("⚠️ Failed to quarantine device: {}", e);
+            }
+        }
+        _ => unreachable!("caller only dispatches known verbs"),
+    }
+}
+
 // Helper function to get readable USB class names
 fn get_class_name(class_code: u8) -> &'static str {
     match class_code {
@@ -403,4 +825,46 @@ fn get_class_name(class_code: u8) -> &'static str {
         0xFF => "Vendor Specific",
         _ => "Unknown",
     }
+}
+
+#[cfg(test)]
+mod ioreg_tests {
+    use super::*;
+
+    // Trimmed excerpt of real `ioreg -p IOUSB -l -w 0` output: tree guides
+    // before the quoted key, the exact shape that broke `starts_with`.
+    const SAMPLE_IOREG_DEVICE: &str = r#"
++-o USB Keyboard@14200000  <class IOUSBHostDevice, id 0x100000severy, registered, matched, active, busy 0 (0 ms), retain 7>
+    {
+      | |   "idVendor" = 1452
+      | |   "idProduct" = 609
+      | |   "locationID" = 320536576
+      | |   "USB Vendor Name" = "Apple Inc."
+      | |   "USB Product Name" = "USB Keyboard"
+    }
+"#;
+
+    #[test]
+    fn extract_ioreg_value_handles_tree_guides() {
+        let lines: Vec<&str> = SAMPLE_IOREG_DEVICE.lines().collect();
+        let vendor_line = lines.iter().find(|l| l.contains("idVendor")).unwrap();
+        let name_line = lines.iter().find(|l| l.contains("USB Vendor Name")).unwrap();
+
+        assert_eq!(extract_ioreg_value(vendor_line, "idVendor"), Some("1452".to_string()));
+        assert_eq!(
+            extract_ioreg_value(name_line, "USB Vendor Name"),
+            Some("Apple Inc.".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_ioreg_usb_devices_extracts_real_fixture() {
+        let devices = parse_ioreg_usb_devices(SAMPLE_IOREG_DEVICE);
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].vendor_id, Some(1452));
+        assert_eq!(devices[0].product_id, Some(609));
+        assert_eq!(devices[0].location_id.as_deref(), Some("320536576"));
+        assert_eq!(devices[0].manufacturer.as_deref(), Some("Apple Inc."));
+        assert_eq!(devices[0].product_name.as_deref(), Some("USB Keyboard"));
+    }
 }
\ No newline at end of file
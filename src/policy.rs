@@ -0,0 +1,130 @@
+// Vendor/product fingerprint database with allow/deny/watch policy.
+//
+// Loads a user-supplied TOML or JSON config mapping `vendor_id:product_id`
+// (optionally narrowed by serial number) to a human-readable name and a
+// policy tag. Ships with a small seed list of well-known attack-tool IDs so
+// the tool is useful before a user ever writes their own config.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Policy {
+    Allow,
+    Deny,
+    Watch,
+}
+
+/// One entry as it appears in the config file, with hex-string IDs.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeviceFingerprint {
+    pub vendor_id: String,
+    pub product_id: String,
+    pub serial_number: Option<String>,
+    pub name: String,
+    pub policy: Policy,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+struct FingerprintFile {
+    #[serde(default)]
+    devices: Vec<DeviceFingerprint>,
+}
+
+/// A resolved entry, keyed by numeric vendor/product ID for fast lookup.
+#[derive(Debug, Clone)]
+struct ResolvedFingerprint {
+    serial_number: Option<String>,
+    name: String,
+    policy: Policy,
+}
+
+#[derive(Debug, Clone)]
+pub struct PolicyVerdict {
+    pub policy: Policy,
+    pub name: Option<String>,
+}
+
+/// Well-known BadUSB/attack-tool vendor:product IDs, shipped so the table is
+/// useful without any user configuration. Extend via a user config file
+/// rather than editing this list for one-off devices.
+const BUILTIN_DENYLIST: &[(u16, u16, &str)] = &[
+    (0x2341, 0x8036, "Arduino Leonardo (common HID-injection platform)"),
+    (0x1b4f, 0x9206, "SparkFun Pro Micro (common HID-injection platform)"),
+    (0x16c0, 0x0483, "Teensyduino (common HID-injection platform)"),
+    (0x0483, 0x5740, "USB Rubber Ducky (CDC/HID variant)"),
+    (0x3553, 0x4103, "O.MG Cable"),
+];
+
+pub struct FingerprintDb {
+    entries: HashMap<(u16, u16), Vec<ResolvedFingerprint>>,
+}
+
+impl FingerprintDb {
+    /// Build a database containing only the built-in seed list.
+    pub fn builtin() -> Self {
+        let mut entries: HashMap<(u16, u16), Vec<ResolvedFingerprint>> = HashMap::new();
+        for &(vendor_id, product_id, name) in BUILTIN_DENYLIST {
+            entries.entry((vendor_id, product_id)).or_default().push(ResolvedFingerprint {
+                serial_number: None,
+                name: name.to_string(),
+                policy: Policy::Deny,
+            });
+        }
+        FingerprintDb { entries }
+    }
+
+    /// Load a user config file (TOML or JSON, chosen by extension) and merge
+    /// it on top of the built-in seed list. User entries for the same
+    /// vendor/product ID take precedence over the seed list.
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let mut db = FingerprintDb::builtin();
+
+        let contents = std::fs::read_to_string(path)?;
+        let file: FingerprintFile = match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => serde_json::from_str(&contents)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?,
+            _ => toml::from_str(&contents)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?,
+        };
+
+        for device in file.devices {
+            let Ok(vendor_id) = u16::from_str_radix(&device.vendor_id, 16) else {
+                continue;
+            };
+            let Ok(product_id) = u16::from_str_radix(&device.product_id, 16) else {
+                continue;
+            };
+            db.entries.entry((vendor_id, product_id)).or_default().push(ResolvedFingerprint {
+                serial_number: device.serial_number,
+                name: device.name,
+                policy: device.policy,
+            });
+        }
+
+        Ok(db)
+    }
+
+    /// Look up the policy and human-readable name for a device. When a
+    /// serial number is provided and an entry specifies one, only an exact
+    /// serial match is used; otherwise the most recently loaded entry for
+    /// the vendor/product ID wins (user config over built-in seed list).
+    pub fn lookup(&self, vendor_id: u16, product_id: u16, serial_number: &str) -> Option<PolicyVerdict> {
+        let candidates = self.entries.get(&(vendor_id, product_id))?;
+
+        let by_serial = candidates.iter().find(|c| {
+            c.serial_number
+                .as_deref()
+                .is_some_and(|s| s == serial_number)
+        });
+
+        let matched = by_serial.or_else(|| candidates.iter().rev().find(|c| c.serial_number.is_none()));
+
+        matched.map(|c| PolicyVerdict {
+            policy: c.policy,
+            name: Some(c.name.clone()),
+        })
+    }
+}
@@ -0,0 +1,275 @@
+// Device authorization/quarantine subsystem.
+//
+// On Linux, a USB device can be deauthorized by writing `0` to the
+// `authorized` file under its sysfs directory
+// (`/sys/bus/usb/devices/<bus>-<port>/authorized`); writing `1` restores it.
+// This module wraps that mechanism behind `enable`/`disable`/`list` verbs and
+// remembers the original authorization state — both the device's and, when
+// auto-quarantine also locks down the controller's `authorized_default`,
+// the controller's — so a quarantined device can always be put back exactly
+// the way it was found.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Key used to look up a quarantine record: `vendor_id:product_id`.
+pub type DeviceKey = (u16, u16);
+
+#[derive(Debug, Clone)]
+pub struct QuarantineRecord {
+    pub sysfs_path: PathBuf,
+    /// Authorization value read before we touched the device (`0` or `1`).
+    pub original_authorized: u8,
+    pub currently_quarantined: bool,
+    /// Set when quarantining this device also disabled the controller's
+    /// `authorized_default`, so `enable` knows what to restore.
+    pub controller_sysfs_path: Option<PathBuf>,
+    pub controller_original_authorized_default: Option<u8>,
+}
+
+/// Where quarantine state is persisted between CLI invocations, so `enable`
+/// and `list` work against devices quarantined by an earlier run.
+const STATE_PATH: &str = "/var/lib/usb-monitor/quarantine.state";
+
+/// Tracks which devices have been quarantined so the action can be reversed.
+pub struct QuarantineManager {
+    records: HashMap<DeviceKey, QuarantineRecord>,
+}
+
+impl QuarantineManager {
+    pub fn new() -> Self {
+        QuarantineManager {
+            records: HashMap::new(),
+        }
+    }
+
+    /// Load persisted quarantine state from [`STATE_PATH`]. Missing or
+    /// unreadable state is treated as "no records yet" rather than an error.
+    pub fn load() -> Self {
+        let mut manager = QuarantineManager::new();
+
+        let Ok(contents) = fs::read_to_string(STATE_PATH) else {
+            return manager;
+        };
+
+        for line in contents.lines() {
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() != 6 {
+                continue;
+            }
+            let (Ok(vendor_id), Ok(product_id), Ok(original_authorized)) = (
+                u16::from_str_radix(fields[0], 16),
+                u16::from_str_radix(fields[1], 16),
+                fields[3].parse::<u8>(),
+            ) else {
+                continue;
+            };
+            let controller_sysfs_path = (!fields[4].is_empty()).then(|| PathBuf::from(fields[4]));
+            let controller_original_authorized_default =
+                (!fields[5].is_empty()).then(|| fields[5].parse::<u8>().ok()).flatten();
+
+            manager.records.insert(
+                (vendor_id, product_id),
+                QuarantineRecord {
+                    sysfs_path: PathBuf::from(fields[2]),
+                    original_authorized,
+                    currently_quarantined: true,
+                    controller_sysfs_path,
+                    controller_original_authorized_default,
+                },
+            );
+        }
+
+        manager
+    }
+
+    /// Persist current quarantine state to [`STATE_PATH`] so a later `enable`
+    /// or `list` invocation can see it. Best-effort: a failure to persist
+    /// (e.g. no write access) is logged but does not abort the caller.
+    pub fn save(&self) {
+        let mut contents = String::new();
+        for ((vendor_id, product_id), record) in &self.records {
+            if !record.currently_quarantined {
+                continue;
+            }
+            contents.push_str(&format!(
+                "{:04x}\t{:04x}\t{}\t{}\t{}\t{}\n",
+                vendor_id,
+                product_id,
+                record.sysfs_path.display(),
+                record.original_authorized,
+                record
+                    .controller_sysfs_path
+                    .as_ref()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_default(),
+                record
+                    .controller_original_authorized_default
+                    .map(|v| v.to_string())
+                    .unwrap_or_default(),
+            ));
+        }
+
+        if let Some(parent) = PathBuf::from(STATE_PATH).parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        if let Err(e) = fs::write(STATE_PATH, contents) {
+            eprintln!("⚠️ Failed to persist quarantine state: {}", e);
+        }
+    }
+
+    /// Deauthorize the device at `sysfs_path` by writing `0` to its
+    /// `authorized` file. Remembers the original value so `enable` can
+    /// restore it later. If the device is already quarantined (e.g.
+    /// re-detected after a restart), the first-seen `original_authorized`
+    /// and controller state are preserved rather than overwritten with the
+    /// already-deauthorized current value.
+    pub fn disable(&mut self, key: DeviceKey, sysfs_path: &Path) -> io::Result<()> {
+        let authorized_file = sysfs_path.join("authorized");
+
+        let (original_authorized, controller_sysfs_path, controller_original_authorized_default) =
+            match self.records.get(&key) {
+                Some(existing) if existing.currently_quarantined => (
+                    existing.original_authorized,
+                    existing.controller_sysfs_path.clone(),
+                    existing.controller_original_authorized_default,
+                ),
+                _ => (read_authorized(&authorized_file).unwrap_or(1), None, None),
+            };
+
+        fs::write(&authorized_file, b"0")?;
+
+        self.records.insert(
+            key,
+            QuarantineRecord {
+                sysfs_path: sysfs_path.to_path_buf(),
+                original_authorized,
+                currently_quarantined: true,
+                controller_sysfs_path,
+                controller_original_authorized_default,
+            },
+        );
+
+        println!(
+            "🚫 Quarantined device {:04x}:{:04x} (was authorized={})",
+            key.0, key.1, original_authorized
+        );
+
+        self.save();
+        Ok(())
+    }
+
+    /// Also deauthorize future devices on the same USB controller by writing
+    /// `0` to `authorized_default` on the controller's sysfs node. Remembers
+    /// the controller's original value against `key`'s quarantine record so
+    /// `enable` can restore it — otherwise the whole bus would be left
+    /// refusing new devices with no way back.
+    pub fn disable_controller_default(&mut self, key: DeviceKey, controller_sysfs_path: &Path) -> io::Result<()> {
+        let authorized_default_file = controller_sysfs_path.join("authorized_default");
+        let original_authorized_default = read_authorized(&authorized_default_file).unwrap_or(1);
+
+        fs::write(&authorized_default_file, b"0")?;
+
+        if let Some(record) = self.records.get_mut(&key) {
+            // Keep the first-seen value on re-quarantine; the current one is
+            // already `0` from the earlier lockdown.
+            if record.controller_original_authorized_default.is_none() {
+                record.controller_sysfs_path = Some(controller_sysfs_path.to_path_buf());
+                record.controller_original_authorized_default = Some(original_authorized_default);
+            }
+        }
+
+        self.save();
+        Ok(())
+    }
+
+    /// Re-authorize a previously quarantined device, restoring whatever
+    /// `authorized` value it had before quarantine (defaults to `1` if we
+    /// have no record of it), and — if quarantining this device also
+    /// disabled the controller's `authorized_default` — restoring that too.
+    pub fn enable(&mut self, key: DeviceKey) -> io::Result<()> {
+        let Some(record) = self.records.get_mut(&key) else {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no quarantine record for {:04x}:{:04x}", key.0, key.1),
+            ));
+        };
+
+        let authorized_file = record.sysfs_path.join("authorized");
+        let restore_value = record.original_authorized.to_string();
+        fs::write(&authorized_file, restore_value.as_bytes())?;
+        record.currently_quarantined = false;
+
+        println!(
+            "✅ Restored device {:04x}:{:04x} to authorized={}",
+            key.0, key.1, record.original_authorized
+        );
+
+        if let (Some(controller_path), Some(original_default)) = (
+            record.controller_sysfs_path.clone(),
+            record.controller_original_authorized_default,
+        ) {
+            let authorized_default_file = controller_path.join("authorized_default");
+            fs::write(&authorized_default_file, original_default.to_string().as_bytes())?;
+            println!(
+                "✅ Restored controller {} authorized_default={}",
+                controller_path.display(),
+                original_default
+            );
+        }
+
+        self.save();
+        Ok(())
+    }
+
+    /// List all devices this manager has ever quarantined, and whether they
+    /// are currently quarantined.
+    pub fn list(&self) -> Vec<(DeviceKey, &QuarantineRecord)> {
+        self.records.iter().map(|(k, v)| (*k, v)).collect()
+    }
+}
+
+/// Parse a `vendor_id:product_id` key, e.g. "05ac:8289", into a [`DeviceKey`].
+pub fn parse_key(s: &str) -> Option<DeviceKey> {
+    let (vendor, product) = s.split_once(':')?;
+    let vendor_id = u16::from_str_radix(vendor, 16).ok()?;
+    let product_id = u16::from_str_radix(product, 16).ok()?;
+    Some((vendor_id, product_id))
+}
+
+fn read_authorized(authorized_file: &Path) -> io::Result<u8> {
+    let contents = fs::read_to_string(authorized_file)?;
+    contents
+        .trim()
+        .parse::<u8>()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Build the sysfs path for a device from its bus number and port chain,
+/// using the kernel's `<bus>-<port>[.<port>...]` naming, e.g. `1-2` or `1-2.4`.
+///
+/// `rusb` doesn't expose the kernel's dotted port path directly, so callers
+/// pass `Device::port_numbers()` and this function joins it to the bus number.
+pub fn sysfs_path_for(bus_number: u8, port_numbers: &[u8]) -> PathBuf {
+    // First port is joined to the bus number with '-'; any further ports in
+    // a hub chain are joined with '.' (e.g. "1-2.4").
+    let mut path_str = bus_number.to_string();
+    let mut ports = port_numbers.iter();
+    if let Some(first) = ports.next() {
+        path_str.push('-');
+        path_str.push_str(&first.to_string());
+        for port in ports {
+            path_str.push('.');
+            path_str.push_str(&port.to_string());
+        }
+    }
+    PathBuf::from("/sys/bus/usb/devices").join(path_str)
+}
+
+/// Sysfs path for the device's root hub controller, used for
+/// `authorized_default`.
+pub fn controller_sysfs_path(bus_number: u8) -> PathBuf {
+    PathBuf::from("/sys/bus/usb/devices").join(format!("usb{}", bus_number))
+}